@@ -0,0 +1,45 @@
+// Configuration loading
+//
+// Ox is configured through a `ron` file (see `ox.ron` in the user's config
+// directory). This module only deals with deserialising that file into a
+// `Config` struct; the editor falls back to `Config::default()` when no
+// file is present or it fails to parse.
+
+use ron::de::from_str;
+use serde::Deserialize;
+use std::fs;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct Config {
+    pub tab_width: usize,
+    pub wrap_width: usize,
+    // Force files detected as binary to be opened as plain text instead of
+    // the read-only hex view
+    pub force_raw_open: bool,
+    // How many keypresses to wait between swap file flushes
+    pub swap_interval: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            tab_width: 4,
+            wrap_width: 80,
+            force_raw_open: false,
+            swap_interval: 50,
+        }
+    }
+}
+
+impl Config {
+    // Read and parse the config file at `path`, falling back to defaults
+    // on any error so a broken config never stops Ox from starting
+    pub fn open(path: &str) -> Self {
+        let path = shellexpand::tilde(path).to_string();
+        fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+}
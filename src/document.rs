@@ -0,0 +1,253 @@
+// A single open file: its rows, dirty state and on-disk location
+
+use crate::editor::Position;
+use crate::row::Row;
+use crate::undo::EventStack;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{Error, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+// Number of bytes inspected from the start of a file to decide whether it
+// looks like text or binary data
+const BINARY_SNIFF_LEN: usize = 1024;
+
+// Snapshot of a document's unsaved state, flushed periodically to a swap
+// file so a panic doesn't lose in-progress edits. Written through
+// `OpenOptions` rather than any Unix-specific temp dir API so it works the
+// same way under the `wasi` build.
+#[derive(Serialize, Deserialize)]
+struct SwapSnapshot {
+    lines: Vec<String>,
+    cursor: Position,
+    dirty: bool,
+}
+
+// The swap file for `/a/b/foo.txt` is the sibling `/a/b/.foo.txt.swp`
+pub fn swap_path(filename: &str) -> PathBuf {
+    let path = Path::new(filename);
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let name = path
+        .file_name()
+        .map_or_else(|| "unnamed".to_string(), |n| n.to_string_lossy().to_string());
+    dir.join(format!(".{}.swp", name))
+}
+
+// Remove a stale swap file for `filename`, used when the user declines
+// recovery at startup
+pub fn delete_swap_file(filename: &str) {
+    let _ = fs::remove_file(swap_path(filename));
+}
+
+// Serialize `lines`/`cursor`/`dirty` straight to `filename`'s swap file.
+// Shared by `Document::write_swap` (periodic flush) and the panic hook's
+// last-resort flush, which only has a shadow copy of the buffer rather
+// than a live `Document` to call a method on.
+pub fn write_swap_direct(
+    filename: &str,
+    lines: &[String],
+    cursor: Position,
+    dirty: bool,
+) -> Result<(), Error> {
+    let snapshot = SwapSnapshot {
+        lines: lines.to_vec(),
+        cursor,
+        dirty,
+    };
+    let serialized =
+        ron::ser::to_string(&snapshot).map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(swap_path(filename))?;
+    file.write_all(serialized.as_bytes())
+}
+
+// Does a swap file exist next to `filename` and is it newer than the real
+// file? Used at startup to decide whether to offer crash recovery.
+pub fn swap_is_newer(filename: &str) -> bool {
+    let swap = swap_path(filename);
+    let (Ok(swap_meta), Ok(real_meta)) = (fs::metadata(&swap), fs::metadata(filename)) else {
+        return swap.exists();
+    };
+    let (Ok(swap_time), Ok(real_time)) = (swap_meta.modified(), real_meta.modified()) else {
+        return false;
+    };
+    swap_time > real_time
+}
+
+#[derive(Default)]
+pub struct Document {
+    pub rows: Vec<Row>,
+    pub file_name: Option<String>,
+    pub dirty: bool,
+    pub cursor: Position,
+    pub offset: Position,
+    pub undo_stack: EventStack,
+    // Binary files are opened read-only in hex view so they can't be
+    // accidentally garbled by treating their bytes as text
+    pub read_only: bool,
+    pub binary: bool,
+}
+
+impl Document {
+    pub fn open(filename: &str) -> Result<Self, Error> {
+        Self::open_with(filename, false)
+    }
+
+    // `force_raw` lets the caller (driven by the `force_raw_open` config
+    // flag) open a file detected as binary as plain text anyway
+    pub fn open_with(filename: &str, force_raw: bool) -> Result<Self, Error> {
+        // Only resurrect a swap that is actually newer than the real file.
+        // The user is asked, and a declined swap deleted, before this point:
+        // by main's `scan_for_swap_recovery` for the CLI file list and by
+        // `Editor::open_document` for the tree/picker, so by the time we
+        // get here a surviving newer swap means recovery is wanted.
+        if swap_is_newer(filename) {
+            if let Some(snapshot) = Self::read_swap(filename) {
+                return Ok(Self {
+                    rows: snapshot.lines.iter().map(|l| Row::from(l.as_str())).collect(),
+                    file_name: Some(filename.to_string()),
+                    dirty: snapshot.dirty,
+                    cursor: snapshot.cursor,
+                    offset: Position::default(),
+                    undo_stack: EventStack::new(),
+                    read_only: false,
+                    binary: false,
+                });
+            }
+        }
+
+        let contents = fs::read(filename)?;
+        let binary = !force_raw && is_binary(&contents);
+
+        let (rows, read_only) = if binary {
+            (hex_rows(&contents), true)
+        } else {
+            let text = String::from_utf8_lossy(&contents);
+            (text.lines().map(Row::from).collect(), false)
+        };
+
+        Ok(Self {
+            rows,
+            file_name: Some(filename.to_string()),
+            dirty: false,
+            cursor: Position::default(),
+            offset: Position::default(),
+            undo_stack: EventStack::new(),
+            read_only,
+            binary,
+        })
+    }
+
+    // Read back a swap file left by a previous crash, if one is still
+    // present next to `filename` (callers are expected to have already
+    // deleted it if the user declined recovery at startup)
+    fn read_swap(filename: &str) -> Option<SwapSnapshot> {
+        let mut contents = String::new();
+        OpenOptions::new()
+            .read(true)
+            .open(swap_path(filename))
+            .ok()?
+            .read_to_string(&mut contents)
+            .ok()?;
+        ron::de::from_str(&contents).ok()
+    }
+
+    // Flush the current buffer contents, cursor and dirty state to the
+    // swap file so a later crash can recover them
+    pub fn write_swap(&self) -> Result<(), Error> {
+        let Some(file_name) = &self.file_name else {
+            return Ok(());
+        };
+        let lines: Vec<String> = self.rows.iter().map(|r| r.string.clone()).collect();
+        write_swap_direct(file_name, &lines, self.cursor, self.dirty)
+    }
+
+    // Remove this document's swap file; called after a clean save or a
+    // graceful exit, since the swap is only useful for crash recovery
+    pub fn delete_swap(&self) {
+        if let Some(file_name) = &self.file_name {
+            let _ = fs::remove_file(swap_path(file_name));
+        }
+    }
+
+    pub fn new() -> Self {
+        Self {
+            rows: vec![Row::default()],
+            file_name: None,
+            dirty: false,
+            cursor: Position::default(),
+            offset: Position::default(),
+            undo_stack: EventStack::new(),
+            read_only: false,
+            binary: false,
+        }
+    }
+
+    pub fn row(&self, index: usize) -> Option<&Row> {
+        self.rows.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn save(&mut self) -> Result<(), Error> {
+        if self.read_only {
+            return Err(Error::new(ErrorKind::PermissionDenied, "buffer is read-only"));
+        }
+        if let Some(file_name) = &self.file_name {
+            let mut file = fs::File::create(file_name)?;
+            for row in &self.rows {
+                file.write_all(row.string.as_bytes())?;
+                file.write_all(b"\n")?;
+            }
+            self.dirty = false;
+            self.delete_swap();
+            Ok(())
+        } else {
+            Err(Error::new(ErrorKind::NotFound, "no file name set"))
+        }
+    }
+}
+
+// A file is treated as binary if a NUL byte shows up in the first
+// `BINARY_SNIFF_LEN` bytes, or that prefix contains a genuinely invalid
+// UTF-8 byte sequence. A multi-byte character straddling the end of the
+// sniffed prefix just looks *incomplete*, not invalid, so it isn't
+// treated as a binary marker.
+fn is_binary(contents: &[u8]) -> bool {
+    let sniff = &contents[..contents.len().min(BINARY_SNIFF_LEN)];
+    if sniff.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(sniff) {
+        Ok(_) => false,
+        Err(e) => e.error_len().is_some(),
+    }
+}
+
+// Render a binary file as fixed-width hex dump rows: an offset column,
+// sixteen space-separated hex byte pairs, then an ASCII gutter with
+// non-printable bytes shown as `.`
+fn hex_rows(contents: &[u8]) -> Vec<Row> {
+    contents
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let offset = i * 16;
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| if (0x20..0x7f).contains(&b) { b as char } else { '.' })
+                .collect();
+            Row::from(format!("{:08x}  {:<47}  {}", offset, hex.join(" "), ascii).as_str())
+        })
+        .collect()
+}
@@ -0,0 +1,574 @@
+// The main editor state and event loop
+//
+// `Editor` owns the terminal and the set of documents currently open, each
+// with its own cursor. When Ox is started on a directory rather than a single file, it
+// also owns a project file-tree that drives a sidebar panel.
+
+use crate::config::Config;
+use crate::document::Document;
+use crate::oxa::{self, Command};
+use crate::terminal::Terminal;
+use clap::App;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use termion::event::Key;
+
+// Mirror of every dirty document's unsaved content, kept up to date on
+// every keypress so the panic hook (set up long before any `Editor` exists,
+// and with no way to reach one once it does) can still flush swap files
+// for whatever was last typed instead of waiting on the periodic
+// `flush_swaps_if_due` schedule
+type PanicRegistry = HashMap<PathBuf, (Vec<String>, Position, bool)>;
+static PANIC_SWAP_REGISTRY: OnceLock<Mutex<PanicRegistry>> = OnceLock::new();
+
+// Write every dirty buffer's last known contents straight to its swap
+// file; called from the panic hook in `main` right before it exits
+pub fn flush_all_swaps_on_panic() {
+    let Some(registry) = PANIC_SWAP_REGISTRY.get() else {
+        return;
+    };
+    let Ok(registry) = registry.lock() else {
+        return;
+    };
+    for (path, (lines, cursor, dirty)) in registry.iter() {
+        if *dirty && !path.as_os_str().is_empty() {
+            let _ = crate::document::write_swap_direct(&path.to_string_lossy(), lines, *cursor, *dirty);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Position {
+    pub x: usize,
+    pub y: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+// Width, in columns, reserved for the project tree sidebar when it is shown
+const TREE_WIDTH: u16 = 24;
+
+pub struct Editor {
+    terminal: Terminal,
+    config: Config,
+    quit: bool,
+    status_message: String,
+    // Project mode: the directory Ox was started on, and the flattened,
+    // currently visible list of paths under it
+    project_root: Option<PathBuf>,
+    tree_entries: Vec<PathBuf>,
+    tree_selected: usize,
+    // First tree entry currently scrolled into view, so the selection
+    // never moves past the bottom (or top) of the sidebar
+    tree_offset: usize,
+    show_tree: bool,
+    // Every document that has been opened this session, keyed by its path
+    // so switching buffers preserves unsaved edits and cursor position
+    documents: HashMap<PathBuf, Document>,
+    active: Option<PathBuf>,
+    // Counts keypresses since the last swap flush; reset once it reaches
+    // `config.swap_interval`
+    swap_counter: u32,
+}
+
+impl Editor {
+    pub fn new(cli: App) -> Result<Self, Error> {
+        let matches = cli.get_matches();
+        let config = Config::open(matches.value_of("config").unwrap_or("~/.config/ox/ox.ron"));
+        let terminal = Terminal::new()?;
+
+        let mut editor = Self {
+            terminal,
+            config,
+            quit: false,
+            status_message: String::new(),
+            project_root: None,
+            tree_entries: Vec::new(),
+            tree_selected: 0,
+            tree_offset: 0,
+            show_tree: false,
+            documents: HashMap::new(),
+            active: None,
+            swap_counter: 0,
+        };
+
+        if let Some(files) = matches.values_of("files") {
+            for file in files {
+                // Recovery for these files was already decided by main's
+                // `scan_for_swap_recovery` before the terminal went raw, so
+                // don't ask about them a second time here
+                editor.open_path_with_jump(file, false)?;
+            }
+        }
+
+        if editor.active.is_none() {
+            let doc = Document::new();
+            editor.documents.insert(PathBuf::new(), doc);
+            editor.active = Some(PathBuf::new());
+        }
+
+        Ok(editor)
+    }
+
+    // Switch into project mode: remember the root and build the initial
+    // flattened tree, then open the first file found so there's always an
+    // active buffer behind the sidebar
+    fn open_project(&mut self, root: &Path) -> Result<(), Error> {
+        self.project_root = Some(root.to_path_buf());
+        self.show_tree = true;
+        self.refresh_tree();
+        if let Some(first) = self.tree_entries.first().cloned() {
+            self.open_document(&first)?;
+        }
+        Ok(())
+    }
+
+    fn refresh_tree(&mut self) {
+        self.tree_entries.clear();
+        if let Some(root) = self.project_root.clone() {
+            Self::collect_entries(&root, &mut self.tree_entries);
+            self.tree_entries.sort();
+        }
+    }
+
+    fn collect_entries(dir: &Path, out: &mut Vec<PathBuf>) {
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in read_dir.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                Self::collect_entries(&path, out);
+            } else {
+                out.push(path);
+            }
+        }
+    }
+
+    // Open `path`, reusing the cached document if it's already loaded so
+    // that unsaved edits and cursor position survive switching away and
+    // back. Asks before recovering a crash swap, since this is how every
+    // path other than the initial CLI file list reaches a document.
+    fn open_document(&mut self, path: &Path) -> Result<(), Error> {
+        self.open_document_with_recovery_prompt(path, true)
+    }
+
+    fn open_document_with_recovery_prompt(
+        &mut self,
+        path: &Path,
+        prompt_for_swap: bool,
+    ) -> Result<(), Error> {
+        let key = path.to_path_buf();
+        if !self.documents.contains_key(&key) {
+            let filename = key.to_string_lossy().to_string();
+            if prompt_for_swap && crate::document::swap_is_newer(&filename) {
+                let message = format!("Found a newer swap file for {}, recover it? (y/n)", filename);
+                let recover = matches!(self.prompt(&message)?, Some(answer) if answer.eq_ignore_ascii_case("y"));
+                if !recover {
+                    crate::document::delete_swap_file(&filename);
+                }
+            }
+            let doc = Document::open_with(&filename, self.config.force_raw_open)?;
+            self.documents.insert(key.clone(), doc);
+        }
+        self.active = Some(key);
+        Ok(())
+    }
+
+    fn active_document_mut(&mut self) -> Option<&mut Document> {
+        self.active.as_ref().and_then(|key| self.documents.get_mut(key))
+    }
+
+    fn active_document(&self) -> Option<&Document> {
+        self.active.as_ref().and_then(|key| self.documents.get(key))
+    }
+
+    // The title shown in the terminal's title bar: file name plus an
+    // asterisk when the buffer has unsaved changes
+    fn window_title(&self) -> String {
+        let name = self
+            .active
+            .as_ref()
+            .map_or("[No Name]".to_string(), |p| {
+                p.file_name()
+                    .map_or_else(|| "[No Name]".to_string(), |n| n.to_string_lossy().to_string())
+            });
+        let dirty = self.active_document().is_some_and(|d| d.dirty);
+        crate::util::title(&name, dirty)
+    }
+
+    pub fn run(&mut self) {
+        loop {
+            if let Err(error) = self.refresh_screen() {
+                die(&error);
+            }
+            if self.quit {
+                break;
+            }
+            if let Err(error) = self.process_keypress() {
+                die(&error);
+            }
+        }
+    }
+
+    fn refresh_screen(&mut self) -> Result<(), Error> {
+        Terminal::cursor_hide();
+        Terminal::cursor_position(0, 0);
+        if self.quit {
+            Terminal::clear_screen();
+            println!("Goodbye.\r");
+        } else {
+            print!("\x1b]2;{}\x07", self.window_title());
+            Terminal::clear_screen();
+            if self.show_tree {
+                self.draw_tree();
+            }
+            let offset_x = if self.show_tree { TREE_WIDTH } else { 0 };
+            self.draw_document(offset_x);
+            self.draw_status_bar();
+            if self.status_message.is_empty() {
+                if let Some(doc) = self.active_document() {
+                    Terminal::cursor_position(
+                        (doc.cursor.x as u16).saturating_add(offset_x),
+                        doc.cursor.y as u16,
+                    );
+                }
+            } else {
+                // Put the cursor at the end of the status/prompt line so
+                // typing into a `:` command or a y/n prompt is visible
+                Terminal::cursor_position(self.status_message.len() as u16, self.terminal.size().height);
+            }
+        }
+        Terminal::cursor_show();
+        Terminal::flush()
+    }
+
+    // Paint `status_message` into the row Ox reserves below the document
+    // (`Terminal::new` trims two rows off the usable height for exactly
+    // this). This is where the `:` command bar, "Unknown command" and the
+    // dirty-buffer "Save changes to ...? (y/n)" prompt actually show up.
+    fn draw_status_bar(&self) {
+        Terminal::cursor_position(0, self.terminal.size().height);
+        Terminal::clear_current_line();
+        print!("{}", self.status_message);
+    }
+
+    // Paint the active document's visible rows starting at column
+    // `offset_x`, leaving the columns to its left for the tree sidebar
+    fn draw_document(&self, offset_x: u16) {
+        let Some(doc) = self.active_document() else {
+            return;
+        };
+        let height = self.terminal.size().height;
+        let width = self.terminal.size().width.saturating_sub(offset_x) as usize;
+        for y in 0..height {
+            Terminal::cursor_position(offset_x, y);
+            if let Some(row) = doc.row(doc.offset.y + y as usize) {
+                print!("{}", row.render(doc.offset.x, doc.offset.x + width, self.config.tab_width));
+            }
+        }
+    }
+
+    // Draw only the slice of `tree_entries` that fits in the terminal's
+    // usable height, scrolled so `tree_selected` is always on screen
+    fn draw_tree(&self) {
+        let height = self.terminal.size().height as usize;
+        for (i, entry) in self
+            .tree_entries
+            .iter()
+            .enumerate()
+            .skip(self.tree_offset)
+            .take(height)
+        {
+            Terminal::cursor_position(0, (i - self.tree_offset) as u16);
+            let name = entry.file_name().map_or_else(
+                || entry.to_string_lossy().to_string(),
+                |n| n.to_string_lossy().to_string(),
+            );
+            let marker = if i == self.tree_selected { ">" } else { " " };
+            print!("{}{:width$}", marker, name, width = (TREE_WIDTH as usize).saturating_sub(1));
+        }
+    }
+
+    // Keep `tree_offset` such that `tree_selected` stays within the visible
+    // window after moving the selection
+    fn clamp_tree_offset(&mut self) {
+        let height = self.terminal.size().height as usize;
+        if self.tree_selected < self.tree_offset {
+            self.tree_offset = self.tree_selected;
+        } else if self.tree_selected >= self.tree_offset + height {
+            self.tree_offset = self.tree_selected + 1 - height;
+        }
+    }
+
+    fn process_keypress(&mut self) -> Result<(), Error> {
+        let pressed_key = Terminal::read_key()?;
+        match pressed_key {
+            Key::Ctrl('q') => self.attempt_quit()?,
+            Key::Ctrl('t') if self.project_root.is_some() => self.show_tree = !self.show_tree,
+            Key::Down if self.show_tree => {
+                self.tree_selected = self.tree_selected.saturating_add(1).min(self.tree_entries.len().saturating_sub(1));
+                self.clamp_tree_offset();
+            }
+            Key::Up if self.show_tree => {
+                self.tree_selected = self.tree_selected.saturating_sub(1);
+                self.clamp_tree_offset();
+            }
+            Key::Char('\n') if self.show_tree => {
+                if let Some(path) = self.tree_entries.get(self.tree_selected).cloned() {
+                    self.open_document(&path)?;
+                }
+            }
+            Key::Char(':') => {
+                if let Some(input) = self.prompt(":")? {
+                    self.run_command(oxa::interpret(&input))?;
+                }
+            }
+            Key::Char('o') if self.project_root.is_some() => self.open_picker()?,
+            _ => self.handle_document_key(pressed_key),
+        }
+        self.sync_panic_registry();
+        self.flush_swaps_if_due();
+        Ok(())
+    }
+
+    // Refresh the panic-safety mirror with every dirty buffer's current
+    // content. Cheap enough to do on every keystroke, unlike the real
+    // on-disk flush below
+    fn sync_panic_registry(&self) {
+        let registry = PANIC_SWAP_REGISTRY.get_or_init(|| Mutex::new(HashMap::new()));
+        let Ok(mut registry) = registry.lock() else {
+            return;
+        };
+        for (path, doc) in &self.documents {
+            if doc.dirty {
+                let lines: Vec<String> = doc.rows.iter().map(|r| r.string.clone()).collect();
+                registry.insert(path.clone(), (lines, doc.cursor, doc.dirty));
+            } else {
+                registry.remove(path);
+            }
+        }
+    }
+
+    // Periodically persist every dirty buffer's contents to its swap file
+    // so an unexpected panic doesn't lose unsaved work
+    fn flush_swaps_if_due(&mut self) {
+        self.swap_counter += 1;
+        if self.swap_counter < self.config.swap_interval {
+            return;
+        }
+        self.swap_counter = 0;
+        for doc in self.documents.values() {
+            if doc.dirty {
+                let _ = doc.write_swap();
+            }
+        }
+    }
+
+    fn handle_document_key(&mut self, key: Key) {
+        let Some(doc) = self.active_document_mut() else {
+            return;
+        };
+        match key {
+            Key::Up => doc.cursor.y = doc.cursor.y.saturating_sub(1),
+            Key::Down => doc.cursor.y = doc.cursor.y.saturating_add(1).min(doc.len().saturating_sub(1)),
+            Key::Left => doc.cursor.x = doc.cursor.x.saturating_sub(1),
+            Key::Right => doc.cursor.x = doc.cursor.x.saturating_add(1),
+            // Hex/binary buffers are opened `read_only`; the
+            // `!doc.read_only` guards on this arm and the `Backspace` arm
+            // keep mutating keys from editing them
+            Key::Char(c) if !doc.read_only => {
+                if let Some(row) = doc.rows.get_mut(doc.cursor.y) {
+                    if c == '\n' {
+                        let new_row = row.split(doc.cursor.x);
+                        doc.rows.insert(doc.cursor.y + 1, new_row);
+                        doc.cursor.y += 1;
+                        doc.cursor.x = 0;
+                    } else {
+                        row.insert(doc.cursor.x, c);
+                        doc.cursor.x += 1;
+                    }
+                    doc.dirty = true;
+                }
+            }
+            Key::Backspace if !doc.read_only => {
+                if doc.cursor.x > 0 {
+                    if let Some(row) = doc.rows.get_mut(doc.cursor.y) {
+                        row.delete(doc.cursor.x - 1);
+                        doc.cursor.x -= 1;
+                        doc.dirty = true;
+                    }
+                } else if doc.cursor.y > 0 {
+                    let current = doc.rows.remove(doc.cursor.y);
+                    doc.cursor.y -= 1;
+                    if let Some(previous) = doc.rows.get_mut(doc.cursor.y) {
+                        doc.cursor.x = previous.len();
+                        previous.append(&current);
+                        doc.dirty = true;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn run_command(&mut self, command: Command) -> Result<(), Error> {
+        match command {
+            Command::Save => {
+                if let Some(doc) = self.active_document_mut() {
+                    if doc.read_only {
+                        self.status_message = "Buffer is read-only".to_string();
+                    } else {
+                        doc.save()?;
+                    }
+                }
+            }
+            Command::Quit => self.attempt_quit()?,
+            Command::Line(line) => {
+                if let Some(doc) = self.active_document_mut() {
+                    doc.cursor.y = line.saturating_sub(1);
+                }
+            }
+            Command::Open(arg) => self.open_path_with_jump(&arg, true)?,
+            Command::Unknown(cmd) => self.status_message = format!("Unknown command: {}", cmd),
+        }
+        Ok(())
+    }
+
+    // Walk every open buffer looking for unsaved changes and ask the user
+    // about each one before actually quitting
+    fn attempt_quit(&mut self) -> Result<(), Error> {
+        let dirty_paths: Vec<PathBuf> = self
+            .documents
+            .iter()
+            .filter(|(_, doc)| doc.dirty)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for path in dirty_paths {
+            let name = if path.as_os_str().is_empty() {
+                "[No Name]".to_string()
+            } else {
+                path.to_string_lossy().to_string()
+            };
+            let prompt = format!("Save changes to {}? (y/n)", name);
+            if let Some(answer) = self.prompt(&prompt)? {
+                if answer.eq_ignore_ascii_case("y") {
+                    if let Some(doc) = self.documents.get_mut(&path) {
+                        doc.save()?;
+                    }
+                }
+            }
+        }
+
+        // A graceful exit means any swap files left behind are no longer
+        // needed for crash recovery
+        for doc in self.documents.values() {
+            doc.delete_swap();
+        }
+
+        self.quit = true;
+        Ok(())
+    }
+
+    // Turn a `files`-style argument (optionally with a `:line` suffix) into
+    // a document switch, opening a project directory instead if that's
+    // what was given. `prompt_for_swap` is false only for the initial CLI
+    // file list, whose swap recovery was already decided before the
+    // terminal went raw (see `scan_for_swap_recovery` in `main`).
+    fn open_path_with_jump(&mut self, arg: &str, prompt_for_swap: bool) -> Result<(), Error> {
+        let (path, line) = oxa::split_line_jump(arg);
+        let path = PathBuf::from(path);
+        if path.is_dir() {
+            self.open_project(&path)
+        } else {
+            self.open_document_with_recovery_prompt(&path, prompt_for_swap)?;
+            if let Some(line) = line {
+                if let Some(doc) = self.active_document_mut() {
+                    doc.cursor.y = line.saturating_sub(1);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    // Interactive fuzzy-filtered open-file picker, bound to `o` in project
+    // mode. Typing `name:100` both filters on `name` and jumps to line 100
+    // on whichever entry is chosen; already-open buffers come from the
+    // cache rather than being re-read from disk.
+    fn open_picker(&mut self) -> Result<(), Error> {
+        let mut query = String::new();
+        let mut selected = 0;
+        loop {
+            let (filter, line) = oxa::split_line_jump(&query);
+            let matches: Vec<String> = self
+                .tree_entries
+                .iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .filter(|entry| crate::util::fuzzy_match(entry, &filter))
+                .collect();
+            selected = selected.min(matches.len().saturating_sub(1));
+            Terminal::draw_picker(&query, &matches, selected);
+            Terminal::flush()?;
+            match Terminal::read_key()? {
+                Key::Char('\n') => {
+                    if let Some(choice) = matches.get(selected).cloned() {
+                        self.open_document(Path::new(&choice))?;
+                        if let Some(line) = line {
+                            if let Some(doc) = self.active_document_mut() {
+                                doc.cursor.y = line.saturating_sub(1);
+                            }
+                        }
+                    }
+                    break;
+                }
+                Key::Char(c) => query.push(c),
+                Key::Backspace => {
+                    query.pop();
+                }
+                Key::Down => selected = selected.saturating_add(1),
+                Key::Up => selected = selected.saturating_sub(1),
+                Key::Esc => break,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Show `message` in the status bar and read a line of input
+    fn prompt(&mut self, message: &str) -> Result<Option<String>, Error> {
+        let mut input = String::new();
+        loop {
+            self.status_message = format!("{} {}", message, input);
+            self.refresh_screen()?;
+            match Terminal::read_key()? {
+                Key::Char('\n') => break,
+                Key::Char(c) => input.push(c),
+                Key::Backspace => {
+                    input.pop();
+                }
+                Key::Esc => {
+                    self.status_message.clear();
+                    return Ok(None);
+                }
+                _ => {}
+            }
+        }
+        self.status_message.clear();
+        Ok(Some(input))
+    }
+}
+
+fn die(e: &Error) {
+    Terminal::clear_screen();
+    panic!("{}", e);
+}
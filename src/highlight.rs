@@ -0,0 +1,48 @@
+// Basic syntax highlighting
+//
+// A `Row` asks this module to classify each character of its string and
+// keeps the result in `Row::highlighting`. The editor prints rows without
+// applying these colours.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Normal,
+    Comment,
+    String,
+    Number,
+    Keyword,
+    Match,
+}
+
+impl Type {
+    // Map a highlight type onto a termion-friendly foreground colour
+    pub fn to_color(self) -> (u8, u8, u8) {
+        match self {
+            Self::Normal => (255, 255, 255),
+            Self::Comment => (110, 110, 110),
+            Self::String => (149, 213, 178),
+            Self::Number => (220, 163, 163),
+            Self::Keyword => (134, 187, 216),
+            Self::Match => (38, 39, 43),
+        }
+    }
+}
+
+// Produce a highlighting classification for every character in `line`
+pub fn highlight(line: &str) -> Vec<Type> {
+    let mut result = Vec::with_capacity(line.len());
+    let mut in_string = false;
+    for c in line.chars() {
+        if c == '"' {
+            in_string = !in_string;
+            result.push(Type::String);
+        } else if in_string {
+            result.push(Type::String);
+        } else if c.is_ascii_digit() {
+            result.push(Type::Number);
+        } else {
+            result.push(Type::Normal);
+        }
+    }
+    result
+}
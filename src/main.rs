@@ -32,10 +32,7 @@ mod util;
 
 use clap::{App, Arg};
 use directories::BaseDirs;
-use document::Document;
-use editor::{Direction, Editor, Position};
-use oxa::Variable;
-use row::Row;
+use editor::Editor;
 #[cfg(target_os = "wasi")]
 use serde_json::json;
 #[cfg(target_os = "wasi")]
@@ -43,8 +40,7 @@ use std::fs;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::{env, panic};
-use terminal::{Size, Terminal};
-use undo::{Event, EventStack};
+use terminal::Terminal;
 
 // Create log macro
 #[macro_export]
@@ -69,6 +65,10 @@ fn main() {
     log!("Ox started", "Ox has just been started");
     // Set up panic hook in case of unexpected crash
     panic::set_hook(Box::new(|e| {
+        // Flush every dirty buffer's last known content to its swap file
+        // before anything else, so the crash that's about to be reported
+        // doesn't also lose unsaved work
+        editor::flush_all_swaps_on_panic();
         // Reenter canonical mode
         Terminal::exit();
         // Set hook to log crash reason
@@ -102,6 +102,10 @@ fn main() {
         }
     }
     
+    // Before anything else touches them, check whether any of the files
+    // we're about to open left behind a swap file from a previous crash
+    scan_for_swap_recovery();
+
     // Attempt to start an editor instance
     #[cfg(not(target_os = "wasi"))]
     let config_dir = load_config().unwrap_or_else(|| "~/.config/ox/ox.ron".to_string());
@@ -123,9 +127,10 @@ fn main() {
                 .multiple(true)
                 .takes_value(true)
                 .help(
-                    r#"The files you wish to edit
+                    r#"The files (or a project directory) you wish to edit
 You can also provide the line number to jump to by doing this:
-file.txt:100 (This will go to line 100 in file.txt)"#,
+file.txt:100 (This will go to line 100 in file.txt)
+Passing a directory opens it in project mode with a file-tree sidebar"#,
                 ),
         )
         .arg(
@@ -151,6 +156,35 @@ file.txt:100 (This will go to line 100 in file.txt)"#,
     }
 }
 
+// Walk the file arguments (clap hasn't parsed them yet, so just take
+// anything on the command line that doesn't look like a flag) and, for
+// each one with a swap file newer than the real file, ask whether to
+// recover it. Declining deletes the stale swap so `Document::open_with`
+// loads the file from disk as normal.
+fn scan_for_swap_recovery() {
+    for arg in env::args().skip(1) {
+        if arg.starts_with('-') {
+            continue;
+        }
+        let (filename, _) = oxa::split_line_jump(&arg);
+        if !document::swap_is_newer(&filename) {
+            // Any swap here is no newer than the real file, i.e. stale
+            // (the file has been saved since it was written) — clear it out
+            // so it can't later be mistaken for a fresher crash
+            document::delete_swap_file(&filename);
+            continue;
+        }
+        eprint!("Found a newer swap file for {}, recover it? (y/n) ", filename);
+        std::io::stdout().flush().unwrap_or(());
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer).is_ok() && answer.trim().eq_ignore_ascii_case("y")
+        {
+            continue;
+        }
+        document::delete_swap_file(&filename);
+    }
+}
+
 fn load_config() -> Option<String> {
     // Load the configuration file
     let base_dirs = BaseDirs::new()?;
@@ -0,0 +1,61 @@
+// Oxa: the small command language driving Ox's command bar
+//
+// Typing `:` opens a prompt where the user can enter an oxa command
+// such as `save`, `quit` or `line 42`. `interpret` turns that raw string
+// into a `Command` the editor knows how to run.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variable {
+    FileName,
+    Line,
+    Column,
+    Version,
+}
+
+impl Variable {
+    pub fn resolve(self, name: &str, line: usize, column: usize) -> String {
+        match self {
+            Self::FileName => name.to_string(),
+            Self::Line => (line + 1).to_string(),
+            Self::Column => (column + 1).to_string(),
+            Self::Version => crate::VERSION.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Save,
+    Quit,
+    Line(usize),
+    Open(String),
+    Unknown(String),
+}
+
+// Parse a raw oxa command string typed into the command bar
+pub fn interpret(input: &str) -> Command {
+    let mut parts = input.trim().split_whitespace();
+    match parts.next() {
+        Some("save" | "w") => Command::Save,
+        Some("quit" | "q") => Command::Quit,
+        Some("line") => parts
+            .next()
+            .and_then(|n| n.parse::<usize>().ok())
+            .map_or_else(|| Command::Unknown(input.to_string()), Command::Line),
+        Some("open" | "o") => parts
+            .next()
+            .map_or_else(|| Command::Unknown(input.to_string()), |arg| Command::Open(arg.to_string())),
+        _ => Command::Unknown(input.to_string()),
+    }
+}
+
+// Split the `file.txt:100` syntax accepted on the command line into a path
+// and an optional 1-indexed line number to jump to
+pub fn split_line_jump(arg: &str) -> (String, Option<usize>) {
+    if let Some((path, line)) = arg.rsplit_once(':') {
+        if let Ok(line) = line.parse::<usize>() {
+            return (path.to_string(), Some(line));
+        }
+    }
+    (arg.to_string(), None)
+}
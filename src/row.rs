@@ -0,0 +1,111 @@
+// A single line of text within a `Document`
+
+use crate::highlight::{self, Type};
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug, Clone, Default)]
+pub struct Row {
+    pub string: String,
+    pub highlighting: Vec<Type>,
+    len: usize,
+}
+
+impl From<&str> for Row {
+    fn from(slice: &str) -> Self {
+        let mut row = Self {
+            string: String::from(slice),
+            highlighting: Vec::new(),
+            len: 0,
+        };
+        row.update_len();
+        row.highlight();
+        row
+    }
+}
+
+impl Row {
+    // Render the graphemes between `start` and `end`, expanding tab
+    // characters to the next stop `tab_width` columns apart
+    pub fn render(&self, start: usize, end: usize, tab_width: usize) -> String {
+        let end = std::cmp::min(end, self.string.len());
+        let start = std::cmp::min(start, end);
+        let mut result = String::new();
+        let mut column = 0;
+        for (i, grapheme) in self.string.graphemes(true).enumerate() {
+            if i >= end {
+                break;
+            }
+            let width = if grapheme == "\t" {
+                crate::util::tab_width(column, tab_width)
+            } else {
+                1
+            };
+            if i >= start {
+                if grapheme == "\t" {
+                    result.push_str(&" ".repeat(width));
+                } else {
+                    result.push_str(grapheme);
+                }
+            }
+            column += width;
+        }
+        result
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, at: usize, c: char) {
+        if at >= self.len() {
+            self.string.push(c);
+        } else {
+            let mut result: String = self.string.graphemes(true).take(at).collect();
+            let remainder: String = self.string.graphemes(true).skip(at).collect();
+            result.push(c);
+            result.push_str(&remainder);
+            self.string = result;
+        }
+        self.update_len();
+        self.highlight();
+    }
+
+    pub fn delete(&mut self, at: usize) {
+        if at >= self.len() {
+            return;
+        }
+        let mut result: String = self.string.graphemes(true).take(at).collect();
+        let remainder: String = self.string.graphemes(true).skip(at + 1).collect();
+        result.push_str(&remainder);
+        self.string = result;
+        self.update_len();
+        self.highlight();
+    }
+
+    pub fn append(&mut self, new: &Self) {
+        self.string.push_str(&new.string);
+        self.update_len();
+        self.highlight();
+    }
+
+    pub fn split(&mut self, at: usize) -> Self {
+        let beginning: String = self.string.graphemes(true).take(at).collect();
+        let remainder: String = self.string.graphemes(true).skip(at).collect();
+        self.string = beginning;
+        self.update_len();
+        self.highlight();
+        Self::from(remainder.as_str())
+    }
+
+    fn update_len(&mut self) {
+        self.len = self.string.graphemes(true).count();
+    }
+
+    fn highlight(&mut self) {
+        self.highlighting = highlight::highlight(&self.string);
+    }
+}
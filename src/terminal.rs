@@ -0,0 +1,110 @@
+// Thin wrapper around the terminal: raw mode, cursor movement and
+// keyboard input
+
+use std::io::{self, stdout, Write};
+use termion::color;
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::{IntoRawMode, RawTerminal};
+
+pub struct Size {
+    pub width: u16,
+    pub height: u16,
+}
+
+pub struct Terminal {
+    size: Size,
+    _stdout: RawTerminal<std::io::Stdout>,
+}
+
+impl Terminal {
+    pub fn new() -> io::Result<Self> {
+        let size = termion::terminal_size()?;
+        Ok(Self {
+            size: Size {
+                width: size.0,
+                height: size.1.saturating_sub(2),
+            },
+            _stdout: stdout().into_raw_mode()?,
+        })
+    }
+
+    pub fn size(&self) -> &Size {
+        &self.size
+    }
+
+    pub fn clear_screen() {
+        print!("{}", termion::clear::All);
+    }
+
+    pub fn clear_current_line() {
+        print!("{}", termion::clear::CurrentLine);
+    }
+
+    pub fn cursor_position(x: u16, y: u16) {
+        let x = x.saturating_add(1);
+        let y = y.saturating_add(1);
+        print!("{}", termion::cursor::Goto(x, y));
+    }
+
+    pub fn flush() -> io::Result<()> {
+        io::stdout().flush()
+    }
+
+    pub fn read_key() -> io::Result<Key> {
+        loop {
+            if let Some(key) = io::stdin().lock().keys().next() {
+                return key;
+            }
+        }
+    }
+
+    pub fn cursor_hide() {
+        print!("{}", termion::cursor::Hide);
+    }
+
+    pub fn cursor_show() {
+        print!("{}", termion::cursor::Show);
+    }
+
+    pub fn set_bg_color(color: color::Rgb) {
+        print!("{}", color::Bg(color));
+    }
+
+    pub fn reset_bg_color() {
+        print!("{}", color::Bg(color::Reset));
+    }
+
+    pub fn set_fg_color(color: color::Rgb) {
+        print!("{}", color::Fg(color));
+    }
+
+    pub fn reset_fg_color() {
+        print!("{}", color::Fg(color::Reset));
+    }
+
+    // Render the fuzzy open-file picker: a query line followed by the
+    // matching entries, with `selected` highlighted
+    pub fn draw_picker(query: &str, matches: &[String], selected: usize) {
+        Self::cursor_position(0, 0);
+        Self::clear_current_line();
+        print!("Open: {}", query);
+        for (i, entry) in matches.iter().enumerate() {
+            Self::cursor_position(0, (i + 1) as u16);
+            Self::clear_current_line();
+            let marker = if i == selected { ">" } else { " " };
+            print!("{} {}", marker, entry);
+        }
+    }
+
+    // Clear the screen, show the cursor and switch back to the main
+    // screen; used by the panic hook so a crash doesn't leave the user's
+    // shell mangled. Raw mode itself is not changed here.
+    pub fn exit() {
+        Self::clear_screen();
+        Self::cursor_position(0, 0);
+        Self::cursor_show();
+        print!("{}", termion::screen::ToMainScreen);
+        Terminal::flush().unwrap_or(());
+    }
+}
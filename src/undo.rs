@@ -0,0 +1,60 @@
+// Undo / redo support
+//
+// An `Event` describes one edit to a document so that it can be replayed
+// backwards (undo) or forwards again (redo). Events are grouped into an
+// `EventStack` which the `Document` keeps alongside its rows. The editor's
+// key handling does not push events onto this stack.
+
+use crate::editor::Position;
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Insertion(Position, char),
+    Deletion(Position, char),
+    InsertLineAbove(Position),
+    InsertLineBelow(Position),
+    DeleteLine(Position, String),
+    UpdateLine(Position, usize, Box<Event>, String),
+    SpliceUp(Position, Position),
+    SplitDown(Position, Position),
+}
+
+#[derive(Debug, Default)]
+pub struct EventStack {
+    history: Vec<Event>,
+    undone: Vec<Event>,
+}
+
+impl EventStack {
+    pub fn new() -> Self {
+        Self {
+            history: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, event: Event) {
+        self.undone.clear();
+        self.history.push(event);
+    }
+
+    pub fn pop(&mut self) -> Option<Event> {
+        let event = self.history.pop();
+        if let Some(event) = event.clone() {
+            self.undone.push(event);
+        }
+        event
+    }
+
+    pub fn redo(&mut self) -> Option<Event> {
+        let event = self.undone.pop();
+        if let Some(event) = event.clone() {
+            self.history.push(event);
+        }
+        event
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.history.is_empty()
+    }
+}
@@ -0,0 +1,24 @@
+// Small helper functions shared across modules
+
+// Build the window title for `name`: `name - Ox`, prefixed with an
+// asterisk when the buffer has unsaved changes
+pub fn title(name: &str, dirty: bool) -> String {
+    let marker = if dirty { "*" } else { "" };
+    format!("{}{} - Ox", marker, name)
+}
+
+// Work out how many spaces a tab character should expand to, given a
+// starting column and the configured tab width
+pub fn tab_width(column: usize, width: usize) -> usize {
+    width - (column % width)
+}
+
+// Does `text` contain every character of `query`, in order, case
+// insensitively? Powers the open-file picker's fuzzy filtering.
+pub fn fuzzy_match(text: &str, query: &str) -> bool {
+    if query.is_empty() {
+        return true;
+    }
+    let mut chars = text.to_lowercase().chars().collect::<Vec<_>>().into_iter();
+    query.to_lowercase().chars().all(|q| chars.any(|c| c == q))
+}